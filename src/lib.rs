@@ -12,7 +12,7 @@
 //!
 //!fn main() {
 //!		let mut ctx = Context::default(Action::Allow).unwrap();
-//!		let rule = Rule::new(105 /* setuid on x86_64 */,
+//!		let rule = Rule::new(Syscall::setuid,
 //!			Compare::arg(0)
 //! 			    .with(1000)
 //! 				.using(Op::Eq)
@@ -30,6 +30,10 @@
 
 extern crate seccomp_sys;
 extern crate libc;
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_json;
 
 use seccomp_sys::*;
 use std::error::Error;
@@ -37,6 +41,14 @@ use std::fmt;
 use std::convert::Into;
 use seccomp_sys::scmp_compare::*;
 
+mod syscall;
+pub use syscall::{Syscall, resolve_name, resolve_name_arch};
+
+mod oci;
+
+mod notify;
+pub use notify::{Notification, NotificationListener, NotificationResponse};
+
 pub type Cmp = scmp_arg_cmp;
 
 /// Comparison operators
@@ -85,6 +97,9 @@ pub enum Action {
 	Errno(i32),
 	/// Notify a tracing process with the specified value
 	Trace(u32),
+	/// Hand the syscall to a user-space supervisor through the notification
+	/// file descriptor (see [`NotificationListener`](struct.NotificationListener.html)).
+	Notify,
 }
 
 impl Into<libc::uint32_t> for Action {
@@ -95,6 +110,85 @@ impl Into<libc::uint32_t> for Action {
 			Action::Trap => SCMP_ACT_TRAP,
 			Action::Errno(x) => SCMP_ACT_ERRNO(x as u32),
 			Action::Trace(x) => SCMP_ACT_TRACE(x),
+			Action::Notify => notify::SCMP_ACT_NOTIFY,
+		}
+	}
+}
+
+/// Filter attributes that can be tuned before loading.
+#[derive(Debug,Clone,Copy)]
+pub enum Attribute {
+	/// Default action for syscalls without a matching rule
+	ActDefault,
+	/// Action for a syscall from an architecture not in the filter
+	ActBadArch,
+	/// Whether loading the filter forces the NO_NEW_PRIVS bit
+	CtlNnp,
+	/// Whether a single `load()` synchronizes the filter across all threads
+	CtlTsync,
+}
+
+impl Into<scmp_filter_attr> for Attribute {
+	fn into(self) -> scmp_filter_attr {
+		match self {
+			Attribute::ActDefault => scmp_filter_attr::SCMP_FLTATR_ACT_DEFAULT,
+			Attribute::ActBadArch => scmp_filter_attr::SCMP_FLTATR_ACT_BADARCH,
+			Attribute::CtlNnp => scmp_filter_attr::SCMP_FLTATR_CTL_NNP,
+			Attribute::CtlTsync => scmp_filter_attr::SCMP_FLTATR_CTL_TSYNC,
+		}
+	}
+}
+
+/// Architectures a filter can target.
+///
+/// A `Context` starts out covering only the native architecture. Adding an
+/// architecture makes every rule added afterwards be resolved and emitted for
+/// it as well, which is what makes a single filter cover compat ABIs (e.g.
+/// x86_64 together with the x32 and i386 layers) rather than leaving them as a
+/// sandbox-bypassing hole.
+#[derive(Debug,Clone,Copy)]
+pub enum Arch {
+	/// The architecture of the running kernel
+	Native,
+	/// 32-bit x86 (i386)
+	X86,
+	/// 64-bit x86
+	X86_64,
+	/// x32 ABI on an x86_64 kernel
+	X32,
+	/// 32-bit ARM
+	Arm,
+	/// 64-bit ARM
+	Aarch64,
+	/// MIPS o32, big endian
+	Mips,
+	/// MIPS n64, big endian
+	Mips64,
+	/// MIPS n32, big endian
+	Mips64N32,
+	/// MIPS o32, little endian
+	Mipsel,
+	/// MIPS n64, little endian
+	Mipsel64,
+	/// MIPS n32, little endian
+	Mipsel64N32,
+}
+
+impl Into<u32> for Arch {
+	fn into(self) -> u32 {
+		match self {
+			Arch::Native => SCMP_ARCH_NATIVE,
+			Arch::X86 => SCMP_ARCH_X86,
+			Arch::X86_64 => SCMP_ARCH_X86_64,
+			Arch::X32 => SCMP_ARCH_X32,
+			Arch::Arm => SCMP_ARCH_ARM,
+			Arch::Aarch64 => SCMP_ARCH_AARCH64,
+			Arch::Mips => SCMP_ARCH_MIPS,
+			Arch::Mips64 => SCMP_ARCH_MIPS64,
+			Arch::Mips64N32 => SCMP_ARCH_MIPS64N32,
+			Arch::Mipsel => SCMP_ARCH_MIPSEL,
+			Arch::Mipsel64 => SCMP_ARCH_MIPSEL64,
+			Arch::Mipsel64N32 => SCMP_ARCH_MIPSEL64N32,
 		}
 	}
 }
@@ -155,11 +249,15 @@ pub struct Rule {
 }
 
 impl Rule {
-	/// Create new rule for `syscall_nr` using comparison `cmp`.
-	pub fn new(syscall_nr: usize, cmp: Cmp, action: Action) -> Rule {
+	/// Create new rule for `syscall` using comparison `cmp`.
+	///
+	/// `syscall` is anything convertible to a syscall number, so both a raw
+	/// number (`105usize`) and a resolved [`Syscall`](enum.Syscall.html)
+	/// variant (`Syscall::setuid`) work.
+	pub fn new<T: Into<usize>>(syscall: T, cmp: Cmp, action: Action) -> Rule {
 		Rule {
 			action: action,
-			syscall_nr: syscall_nr,
+			syscall_nr: syscall.into(),
 			comparators: vec![cmp]
 		}
 	}
@@ -227,13 +325,151 @@ impl Context {
 		}
 	}
 
+	/// Adds an architecture to the filter.
+	///
+	/// Rules added after this call are resolved and emitted for `arch` in
+	/// addition to the architectures already present, so a single filter can
+	/// span several ABIs. Adding the native architecture again is a no-op.
+	pub fn add_arch(&mut self, arch: Arch) -> Result<(),SeccompError> {
+		let res = unsafe { seccomp_arch_add(self.int, arch.into()) };
+		if res != 0 && res != -libc::EEXIST {
+			Err(SeccompError::new(format!("failed to add architecture {:?}", arch)))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Removes an architecture from the filter.
+	///
+	/// Rules will no longer be emitted for `arch`. Removing an architecture
+	/// that is not present is a no-op.
+	pub fn remove_arch(&mut self, arch: Arch) -> Result<(),SeccompError> {
+		let res = unsafe { seccomp_arch_remove(self.int, arch.into()) };
+		if res != 0 && res != -libc::EEXIST {
+			Err(SeccompError::new(format!("failed to remove architecture {:?}", arch)))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Hints that `syscall` is hot and should be checked early.
+	///
+	/// Seccomp evaluates its BPF program linearly, so a frequently-invoked
+	/// syscall sitting late in a large filter adds overhead to every call.
+	/// Bumping its priority asks libseccomp to order it earlier when generating
+	/// the program; higher values are placed earlier. This is purely a
+	/// performance hint and does not change the policy — the reordering can be
+	/// inspected with [`export_pfc`](#method.export_pfc).
+	pub fn set_priority<T: Into<usize>>(&mut self, syscall: T, priority: u8) -> Result<(),SeccompError> {
+		let syscall_nr = syscall.into();
+		let res = unsafe { seccomp_syscall_priority(self.int, syscall_nr as i32, priority) };
+		if res != 0 {
+			Err(SeccompError::new(format!("failed to set priority for syscall {}", syscall_nr)))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Sets a filter attribute. See [`Attribute`](enum.Attribute.html) and the
+	/// typed helpers [`set_no_new_privs`](#method.set_no_new_privs) and
+	/// [`set_thread_sync`](#method.set_thread_sync).
+	pub fn set_attribute(&mut self, attr: Attribute, value: u32) -> Result<(),SeccompError> {
+		let res = unsafe { seccomp_attr_set(self.int, attr.into(), value) };
+		if res != 0 {
+			Err(SeccompError::new(format!("failed to set attribute {:?}", attr)))
+		} else {
+			Ok(())
+		}
+	}
+
+	/// Controls whether `load()` forces the NO_NEW_PRIVS bit.
+	///
+	/// Loading a filter normally requires either `CAP_SYS_ADMIN` or
+	/// NO_NEW_PRIVS; disabling this lets a privileged process load a filter
+	/// without giving up the ability to gain privileges through `execve`.
+	pub fn set_no_new_privs(&mut self, enabled: bool) -> Result<(),SeccompError> {
+		self.set_attribute(Attribute::CtlNnp, enabled as u32)
+	}
+
+	/// Controls thread synchronization (TSYNC) of the filter.
+	///
+	/// When enabled, a single [`load()`](#method.load) applies the filter to
+	/// every thread in the process rather than just the calling one, which is
+	/// essential for multithreaded daemons.
+	pub fn set_thread_sync(&mut self, enabled: bool) -> Result<(),SeccompError> {
+		self.set_attribute(Attribute::CtlTsync, enabled as u32)
+	}
+
 	/// Loads the filter into the kernel. Rules will be applied when this function returns.
+	///
+	/// With TSYNC enabled a load can fail because a thread could not be
+	/// synchronized. By default libseccomp reports this as a plain error; it
+	/// only surfaces the offending thread's TID as a positive return code when
+	/// the `SCMP_FLTATR_API_SYSRAWRC` attribute is set, which this API does not
+	/// expose. The positive-TID branch below is therefore only reachable for
+	/// callers who enable raw return codes through another path.
 	pub fn load(&self) -> Result<(),SeccompError> {
 		let res = unsafe { seccomp_load(self.int) };
-		if res != 0 {
+		if res == 0 {
+			Ok(())
+		} else if res > 0 {
+			Err(SeccompError::new(format!("failed to synchronize filter to thread {}", res)))
+		} else {
 			Err(SeccompError::new("failed to load filter into the kernel"))
+		}
+	}
+
+	/// Exports the compiled filter as classic BPF to `writer`.
+	///
+	/// The dumped program can be loaded elsewhere via
+	/// `prctl`/`SECCOMP_SET_MODE_FILTER`, for instance in a fork-exec stub.
+	pub fn export_bpf<W: std::io::Write>(&self, writer: W) -> Result<(),SeccompError> {
+		self.export(writer, |ctx, fd| unsafe { seccomp_export_bpf(ctx, fd) })
+	}
+
+	/// Exports the filter as human-readable pseudo-filter-code (PFC) to
+	/// `writer`, handy for inspecting a policy before loading it.
+	pub fn export_pfc<W: std::io::Write>(&self, writer: W) -> Result<(),SeccompError> {
+		self.export(writer, |ctx, fd| unsafe { seccomp_export_pfc(ctx, fd) })
+	}
+
+	fn export<W, F>(&self, mut writer: W, export_fn: F) -> Result<(),SeccompError>
+		where W: std::io::Write,
+			  F: Fn(*const scmp_filter_ctx, libc::c_int) -> libc::c_int {
+		use std::os::unix::io::FromRawFd;
+
+		let name = b"seccomp-export\0";
+		let memfd = unsafe { libc::memfd_create(name.as_ptr() as *const libc::c_char, 0) };
+		if memfd < 0 {
+			return Err(SeccompError::new("failed to allocate export buffer"));
+		}
+		// Take ownership so the fd is closed when this function returns.
+		let mut file = unsafe { std::fs::File::from_raw_fd(memfd) };
+
+		if export_fn(self.int, memfd) != 0 {
+			return Err(SeccompError::new("failed to export filter"));
+		}
+		if unsafe { libc::lseek(memfd, 0, libc::SEEK_SET) } < 0 {
+			return Err(SeccompError::new("failed to rewind export buffer"));
+		}
+		try!(std::io::copy(&mut file, &mut writer)
+			.map_err(|e| SeccompError::new(format!("failed to write export: {}", e))));
+		Ok(())
+	}
+
+	/// Returns the user-notification file descriptor for a loaded filter that
+	/// uses [`Action::Notify`](enum.Action.html#variant.Notify).
+	///
+	/// This must be called after [`load`](#method.load). The descriptor is
+	/// owned by the kernel filter; wrap it in a
+	/// [`NotificationListener`](struct.NotificationListener.html) to service
+	/// intercepted syscalls.
+	pub fn receive_notify_fd(&self) -> Result<::std::os::unix::io::RawFd, SeccompError> {
+		let fd = unsafe { notify::seccomp_notify_fd(self.int) };
+		if fd < 0 {
+			Err(SeccompError::new("failed to obtain notification fd"))
 		} else {
-			Ok(())
+			Ok(fd)
 		}
 	}
 }
@@ -248,7 +484,7 @@ impl Drop for Context {
 fn it_works() {
 	fn test() -> Result<(),Box<Error>> {
 		let mut ctx = try!(Context::default(Action::Allow));
-		try!(ctx.add_rule(Rule::new(105, Compare::arg(0).using(Op::Eq).with(1000).build().unwrap(), Action::Errno(libc::EPERM))));
+		try!(ctx.add_rule(Rule::new(Syscall::setuid, Compare::arg(0).using(Op::Eq).with(1000).build().unwrap(), Action::Errno(libc::EPERM))));
 		try!(ctx.load());
 		let ret = unsafe { libc::setuid(1000) };
 		println!("ret = {}, uid = {}", ret, unsafe { libc::getuid() });
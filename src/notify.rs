@@ -0,0 +1,181 @@
+//! User-space syscall notification (`SCMP_ACT_NOTIFY`).
+//!
+//! A filter loaded with [`Action::Notify`](../enum.Action.html#variant.Notify)
+//! does not decide the fate of a syscall itself; instead it parks the calling
+//! thread and hands the syscall to a supervisor process over a notification
+//! file descriptor. The supervisor receives the syscall number, arguments and
+//! originating PID, decides what to do, and answers back — returning an errno,
+//! substituting a return value, or letting the kernel continue the real
+//! syscall.
+//!
+//! These bindings are declared here because they postdate the `seccomp_sys`
+//! release this crate builds against.
+
+use std::os::unix::io::RawFd;
+use std::ptr;
+
+use seccomp_sys::scmp_filter_ctx;
+use libc;
+
+use ::SeccompError;
+
+/// `SCMP_ACT_NOTIFY` — trigger a user-space notification.
+pub const SCMP_ACT_NOTIFY: u32 = 0x7fc0_0000;
+
+/// `SECCOMP_USER_NOTIF_FLAG_CONTINUE` — let the kernel run the real syscall.
+const SECCOMP_USER_NOTIF_FLAG_CONTINUE: u32 = 1;
+
+#[repr(C)]
+struct seccomp_data {
+	nr: libc::c_int,
+	arch: u32,
+	instruction_pointer: u64,
+	args: [u64; 6],
+}
+
+#[repr(C)]
+struct seccomp_notif {
+	id: u64,
+	pid: u32,
+	flags: u32,
+	data: seccomp_data,
+}
+
+#[repr(C)]
+struct seccomp_notif_resp {
+	id: u64,
+	val: i64,
+	error: i32,
+	flags: u32,
+}
+
+extern "C" {
+	pub fn seccomp_notify_fd(ctx: *const scmp_filter_ctx) -> libc::c_int;
+	fn seccomp_notify_alloc(req: *mut *mut seccomp_notif, resp: *mut *mut seccomp_notif_resp) -> libc::c_int;
+	fn seccomp_notify_free(req: *mut seccomp_notif, resp: *mut seccomp_notif_resp);
+	fn seccomp_notify_receive(fd: libc::c_int, req: *mut seccomp_notif) -> libc::c_int;
+	fn seccomp_notify_respond(fd: libc::c_int, resp: *mut seccomp_notif_resp) -> libc::c_int;
+	fn seccomp_notify_id_valid(fd: libc::c_int, id: u64) -> libc::c_int;
+}
+
+/// A syscall intercepted by the kernel and delivered to the supervisor.
+#[derive(Debug,Clone)]
+pub struct Notification {
+	/// Cookie identifying this request; echo it back in the response and pass
+	/// it to [`id_valid`](struct.NotificationListener.html#method.id_valid).
+	pub id: u64,
+	/// PID of the thread that made the syscall.
+	pub pid: u32,
+	/// Syscall number, in the target thread's architecture.
+	pub syscall: i32,
+	/// The target thread's architecture (`SCMP_ARCH_*`).
+	pub arch: u32,
+	/// The six raw syscall arguments.
+	pub args: [u64; 6],
+}
+
+/// The answer a supervisor sends back for a [`Notification`](struct.Notification.html).
+#[derive(Debug,Clone)]
+pub struct NotificationResponse {
+	id: u64,
+	val: i64,
+	error: i32,
+	flags: u32,
+}
+
+impl NotificationResponse {
+	/// Fail the syscall with `errno`.
+	pub fn errno(notification: &Notification, errno: i32) -> Self {
+		NotificationResponse { id: notification.id, val: 0, error: -errno, flags: 0 }
+	}
+
+	/// Complete the syscall successfully, returning `val` to the target.
+	pub fn success(notification: &Notification, val: i64) -> Self {
+		NotificationResponse { id: notification.id, val: val, error: 0, flags: 0 }
+	}
+
+	/// Let the kernel continue the real syscall.
+	///
+	/// This is inherently racy and must not be used for security decisions on
+	/// the syscall arguments — the target may change them after the check. It
+	/// is meant for syscalls the supervisor only wants to observe.
+	pub fn continue_syscall(notification: &Notification) -> Self {
+		NotificationResponse { id: notification.id, val: 0, error: 0, flags: SECCOMP_USER_NOTIF_FLAG_CONTINUE }
+	}
+}
+
+/// Services the notifications delivered to a filter's notification fd.
+///
+/// Obtain the fd from [`Context::receive_notify_fd`](../struct.Context.html#method.receive_notify_fd)
+/// after loading the filter.
+#[derive(Debug)]
+pub struct NotificationListener {
+	fd: RawFd,
+}
+
+impl NotificationListener {
+	/// Wraps an already-obtained notification file descriptor.
+	pub fn new(fd: RawFd) -> Self {
+		NotificationListener { fd: fd }
+	}
+
+	/// Blocks until the next syscall is intercepted and returns it.
+	pub fn receive(&self) -> Result<Notification, SeccompError> {
+		let mut req: *mut seccomp_notif = ptr::null_mut();
+		let mut resp: *mut seccomp_notif_resp = ptr::null_mut();
+		if unsafe { seccomp_notify_alloc(&mut req, &mut resp) } != 0 {
+			return Err(SeccompError::new("failed to allocate notification buffers"));
+		}
+
+		let res = unsafe { seccomp_notify_receive(self.fd, req) };
+		let notification = if res == 0 {
+			let r = unsafe { &*req };
+			Ok(Notification {
+				id: r.id,
+				pid: r.pid,
+				syscall: r.data.nr,
+				arch: r.data.arch,
+				args: r.data.args,
+			})
+		} else {
+			Err(SeccompError::new("failed to receive notification"))
+		};
+
+		unsafe { seccomp_notify_free(req, resp) };
+		notification
+	}
+
+	/// Sends `response` back to the kernel, unblocking the target thread.
+	pub fn respond(&self, response: &NotificationResponse) -> Result<(), SeccompError> {
+		let mut req: *mut seccomp_notif = ptr::null_mut();
+		let mut resp: *mut seccomp_notif_resp = ptr::null_mut();
+		if unsafe { seccomp_notify_alloc(&mut req, &mut resp) } != 0 {
+			return Err(SeccompError::new("failed to allocate notification buffers"));
+		}
+
+		unsafe {
+			(*resp).id = response.id;
+			(*resp).val = response.val;
+			(*resp).error = response.error;
+			(*resp).flags = response.flags;
+		}
+
+		let res = unsafe { seccomp_notify_respond(self.fd, resp) };
+		unsafe { seccomp_notify_free(req, resp) };
+
+		if res == 0 {
+			Ok(())
+		} else {
+			Err(SeccompError::new("failed to respond to notification"))
+		}
+	}
+
+	/// Reports whether the notification `id` still refers to a live target.
+	///
+	/// Check this before reading the target's memory: if the thread has died,
+	/// its PID may have been recycled, and acting on a stale notification is a
+	/// TOCTOU bug.
+	pub fn id_valid(&self, id: u64) -> bool {
+		unsafe { seccomp_notify_id_valid(self.fd, id) == 0 }
+	}
+}
@@ -0,0 +1,211 @@
+//! Loading of OCI/Docker seccomp JSON profiles.
+//!
+//! Container runtimes describe their seccomp policy as a JSON document with a
+//! `defaultAction` and a `syscalls` array. Each entry names one or more
+//! syscalls, an action, and an optional set of argument comparisons. This
+//! module parses that structure and turns it into a ready-to-load
+//! [`Context`](../struct.Context.html), which makes the crate a drop-in policy
+//! loader for runtimes such as youki.
+
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+use serde_json;
+
+use ::{Action, Cmp, Compare, Context, Op, Rule, SeccompError};
+use syscall::resolve_name;
+
+#[derive(Debug,Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Profile {
+	default_action: String,
+	#[serde(default)]
+	syscalls: Vec<SyscallEntry>,
+}
+
+#[derive(Debug,Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SyscallEntry {
+	names: Vec<String>,
+	action: String,
+	#[serde(default)]
+	args: Vec<Arg>,
+	#[serde(default)]
+	errno_ret: Option<u32>,
+}
+
+#[derive(Debug,Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Arg {
+	index: u32,
+	value: u64,
+	#[serde(default)]
+	value_two: u64,
+	op: String,
+}
+
+/// Maps an OCI action string onto the crate's [`Action`](../enum.Action.html).
+///
+/// `errno_ret` supplies the errno for `SCMP_ACT_ERRNO` / the data for
+/// `SCMP_ACT_TRACE`; the OCI defaults (`EPERM` and `0`) are used when it is
+/// absent.
+///
+/// `SCMP_ACT_LOG` is observe-only and has no `Action` counterpart, so it maps
+/// to `Ok(None)`: the caller drops such an entry rather than failing the whole
+/// profile over it. Truly unknown actions (including `SCMP_ACT_KILL_PROCESS`,
+/// which this crate cannot express) are still an error.
+fn parse_action(name: &str, errno_ret: Option<u32>) -> Result<Option<Action>, SeccompError> {
+	match name {
+		"SCMP_ACT_ALLOW" => Ok(Some(Action::Allow)),
+		"SCMP_ACT_KILL" => Ok(Some(Action::Kill)),
+		"SCMP_ACT_TRAP" => Ok(Some(Action::Trap)),
+		"SCMP_ACT_ERRNO" => Ok(Some(Action::Errno(errno_ret.unwrap_or(::libc::EPERM as u32) as i32))),
+		"SCMP_ACT_TRACE" => Ok(Some(Action::Trace(errno_ret.unwrap_or(0)))),
+		"SCMP_ACT_NOTIFY" => Ok(Some(Action::Notify)),
+		"SCMP_ACT_LOG" => Ok(None),
+		other => Err(SeccompError::new(format!("unsupported action {:?}", other))),
+	}
+}
+
+/// Maps an OCI comparison operator string onto the crate's [`Op`](../enum.Op.html).
+fn parse_op(name: &str) -> Result<Op, SeccompError> {
+	match name {
+		"SCMP_CMP_NE" => Ok(Op::Ne),
+		"SCMP_CMP_LT" => Ok(Op::Lt),
+		"SCMP_CMP_LE" => Ok(Op::Le),
+		"SCMP_CMP_EQ" => Ok(Op::Eq),
+		"SCMP_CMP_GE" => Ok(Op::Ge),
+		"SCMP_CMP_GT" => Ok(Op::Gt),
+		"SCMP_CMP_MASKED_EQ" => Ok(Op::MaskedEq),
+		other => Err(SeccompError::new(format!("unsupported operator {:?}", other))),
+	}
+}
+
+impl Arg {
+	fn to_cmp(&self) -> Result<Cmp, SeccompError> {
+		let op = try!(parse_op(&self.op));
+		Compare::arg(self.index)
+			.using(op)
+			.with(self.value)
+			.and(self.value_two)
+			.build()
+			.ok_or_else(|| SeccompError::new("incomplete argument comparison"))
+	}
+}
+
+impl Context {
+	/// Builds a context from an OCI/Docker seccomp profile read from `reader`.
+	///
+	/// The `defaultAction` becomes the context's default, and every
+	/// `(syscall, arg-set)` pair becomes one rule: an entry listing several
+	/// names expands into several rules sharing the same comparators, and an
+	/// entry with no `args` adds an unconditional rule. Entries whose action
+	/// matches the default are skipped, since libseccomp rejects a rule that
+	/// is indistinguishable from the default action. Following runc and youki,
+	/// a name the local libseccomp cannot resolve and an observe-only
+	/// `SCMP_ACT_LOG` entry are skipped rather than aborting the whole profile.
+	pub fn from_oci_profile<R: Read>(mut reader: R) -> Result<Context, SeccompError> {
+		let mut buf = String::new();
+		try!(reader.read_to_string(&mut buf)
+			.map_err(|e| SeccompError::new(format!("failed to read profile: {}", e))));
+		let profile: Profile = try!(serde_json::from_str(&buf)
+			.map_err(|e| SeccompError::new(format!("failed to parse profile: {}", e))));
+
+		let default_action = try!(parse_action(&profile.default_action, None)
+			.and_then(|a| a.ok_or_else(|| SeccompError::new("SCMP_ACT_LOG is not a valid default action"))));
+		let default_token: u32 = default_action.into();
+		let mut ctx = try!(Context::default(default_action));
+
+		for entry in &profile.syscalls {
+			// A `SCMP_ACT_LOG` entry is observe-only and has no enforceable
+			// action; drop it rather than aborting the whole profile.
+			let action = match try!(parse_action(&entry.action, entry.errno_ret)) {
+				Some(action) => action,
+				None => continue,
+			};
+			// libseccomp rejects a rule whose action equals the filter's
+			// default with `-EACCES`, so an entry matching the default would
+			// abort the whole load on otherwise valid input.
+			let action_token: u32 = action.into();
+			if action_token == default_token {
+				continue;
+			}
+			let comparators: Vec<Cmp> = {
+				let mut v = Vec::with_capacity(entry.args.len());
+				for arg in &entry.args {
+					v.push(try!(arg.to_cmp()));
+				}
+				v
+			};
+
+			for name in &entry.names {
+				// Profiles routinely list syscalls unknown to the installed
+				// libseccomp/arch (e.g. `clone3`, `close_range`); like runc and
+				// youki, skip an unresolved name instead of failing the load.
+				let syscall_nr = match resolve_name(name) {
+					Ok(nr) => nr,
+					Err(_) => continue,
+				};
+				try!(ctx.add_rule(Rule {
+					action: action,
+					syscall_nr: syscall_nr,
+					comparators: comparators.clone(),
+				}));
+			}
+		}
+
+		Ok(ctx)
+	}
+
+	/// Builds a context from an OCI/Docker seccomp profile stored at `path`.
+	///
+	/// Convenience wrapper around
+	/// [`from_oci_profile`](#method.from_oci_profile).
+	pub fn from_oci_profile_path<P: AsRef<Path>>(path: P) -> Result<Context, SeccompError> {
+		let file = try!(File::open(path.as_ref())
+			.map_err(|e| SeccompError::new(format!("failed to open profile: {}", e))));
+		Context::from_oci_profile(file)
+	}
+}
+
+#[test]
+fn parses_profile_structure() {
+	let json = r#"{
+		"defaultAction": "SCMP_ACT_ERRNO",
+		"syscalls": [
+			{ "names": ["read", "write"], "action": "SCMP_ACT_ALLOW" },
+			{
+				"names": ["setuid"],
+				"action": "SCMP_ACT_ALLOW",
+				"args": [
+					{ "index": 0, "value": 1000, "valueTwo": 4095, "op": "SCMP_CMP_MASKED_EQ" }
+				]
+			},
+			{ "names": ["ptrace"], "action": "SCMP_ACT_ERRNO" }
+		]
+	}"#;
+
+	let profile: Profile = serde_json::from_str(json).unwrap();
+	assert_eq!(profile.syscalls.len(), 3);
+
+	// A multi-name entry keeps every name; each one later becomes its own rule.
+	assert_eq!(profile.syscalls[0].names.len(), 2);
+
+	// The `valueTwo`/`op` of an argument comparison survive the round trip.
+	let cmp = profile.syscalls[1].args[0].to_cmp().unwrap();
+	assert_eq!(cmp.arg, 0);
+	assert_eq!(cmp.datum_a, 1000);
+	assert_eq!(cmp.datum_b, 4095);
+	assert_eq!(cmp.op, Op::MaskedEq.into());
+
+	// The final entry's action equals the default, so it is skipped rather
+	// than handed to libseccomp (which would reject it with -EACCES).
+	let default_token: u32 = parse_action(&profile.default_action, None).unwrap().unwrap().into();
+	let skipped_token: u32 = parse_action(&profile.syscalls[2].action, profile.syscalls[2].errno_ret).unwrap().unwrap().into();
+	assert_eq!(default_token, skipped_token);
+
+	// `SCMP_ACT_LOG` is recognized but carries no enforceable action, so it is
+	// dropped rather than rejected.
+	assert!(parse_action("SCMP_ACT_LOG", None).unwrap().is_none());
+}
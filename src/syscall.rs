@@ -0,0 +1,376 @@
+//! Symbolic syscall names and a generated `Syscall` enum.
+//!
+//! Syscall numbers are architecture-specific, so rather than hardcoding a
+//! number like `105` (which only happens to mean `setuid` on x86_64) this
+//! module lets a name be resolved to the right number for an architecture
+//! through libseccomp's own tables.
+
+use seccomp_sys::*;
+use std::ffi::CString;
+
+use ::SeccompError;
+
+/// Resolves a syscall name to its number on the native architecture.
+///
+/// Wraps `seccomp_syscall_resolve_name`. An unknown name resolves to
+/// `__NR_SCMP_ERROR`, which is surfaced here as a `SeccompError`.
+pub fn resolve_name<T: AsRef<str>>(name: T) -> Result<usize, SeccompError> {
+	let cname = try!(CString::new(name.as_ref())
+		.map_err(|_| SeccompError::new("syscall name contains a nul byte")));
+	let nr = unsafe { seccomp_syscall_resolve_name(cname.as_ptr()) };
+	if nr == __NR_SCMP_ERROR {
+		Err(SeccompError::new(format!("unknown syscall {:?}", name.as_ref())))
+	} else {
+		Ok(nr as usize)
+	}
+}
+
+/// Resolves a syscall name to its number on a specific architecture.
+///
+/// Wraps `seccomp_syscall_resolve_name_arch`, which is the right entry point
+/// when a filter is being built for a foreign target rather than the host.
+/// `arch_token` is a libseccomp `SCMP_ARCH_*` value.
+pub fn resolve_name_arch<T: AsRef<str>>(arch_token: u32, name: T) -> Result<usize, SeccompError> {
+	let cname = try!(CString::new(name.as_ref())
+		.map_err(|_| SeccompError::new("syscall name contains a nul byte")));
+	let nr = unsafe { seccomp_syscall_resolve_name_arch(arch_token, cname.as_ptr()) };
+	if nr == __NR_SCMP_ERROR {
+		Err(SeccompError::new(format!("unknown syscall {:?} for arch {}", name.as_ref(), arch_token)))
+	} else {
+		Ok(nr as usize)
+	}
+}
+
+macro_rules! syscalls {
+	($($name:ident),* $(,)*) => {
+		/// Generated enum of known syscalls.
+		///
+		/// Each variant carries no number; it is resolved to the correct
+		/// value for an architecture on demand (see [`resolve_name`] and
+		/// [`resolve_name_arch`]). Converting to `usize` resolves against the
+		/// native architecture and yields `__NR_SCMP_ERROR` for an unknown
+		/// name, so a bogus rule is rejected when it is added to a context
+		/// rather than silently matching syscall zero.
+		#[derive(Debug,Clone,Copy,PartialEq,Eq,Hash)]
+		#[allow(non_camel_case_types)]
+		pub enum Syscall {
+			$($name),*
+		}
+
+		impl Syscall {
+			/// The canonical libseccomp name of this syscall.
+			pub fn name(self) -> &'static str {
+				match self {
+					$(Syscall::$name => stringify!($name)),*
+				}
+			}
+		}
+	}
+}
+
+impl Syscall {
+	/// Resolves this syscall to its number on the native architecture.
+	pub fn resolve(self) -> Result<usize, SeccompError> {
+		resolve_name(self.name())
+	}
+
+	/// Resolves this syscall to its number on the architecture named by
+	/// `arch_token` (a libseccomp `SCMP_ARCH_*` value).
+	pub fn resolve_arch(self, arch_token: u32) -> Result<usize, SeccompError> {
+		resolve_name_arch(arch_token, self.name())
+	}
+}
+
+impl Into<usize> for Syscall {
+	/// Resolves against the native architecture for use in
+	/// [`Rule::new`](../struct.Rule.html#method.new).
+	///
+	/// This is the infallible conversion, so a name the local libseccomp does
+	/// not know yields `__NR_SCMP_ERROR as usize` rather than an error; the
+	/// failure then resurfaces as a "failed to add rule" from
+	/// [`add_rule`](../struct.Context.html#method.add_rule). Use
+	/// [`resolve`](#method.resolve) when the unknown-syscall case needs to be
+	/// handled explicitly as a [`SeccompError`](../struct.SeccompError.html).
+	fn into(self) -> usize {
+		let cname = CString::new(self.name()).unwrap();
+		let nr = unsafe { seccomp_syscall_resolve_name(cname.as_ptr()) };
+		nr as usize
+	}
+}
+
+// The list below follows the x86_64 syscall numbering order. It covers a
+// subset of that table; a syscall missing from it can still be resolved by
+// name with `resolve_name`.
+syscalls! {
+	read,
+	write,
+	open,
+	close,
+	stat,
+	fstat,
+	lstat,
+	poll,
+	lseek,
+	mmap,
+	mprotect,
+	munmap,
+	brk,
+	rt_sigaction,
+	rt_sigprocmask,
+	rt_sigreturn,
+	ioctl,
+	pread64,
+	pwrite64,
+	readv,
+	writev,
+	access,
+	pipe,
+	select,
+	sched_yield,
+	mremap,
+	msync,
+	mincore,
+	madvise,
+	shmget,
+	shmat,
+	shmctl,
+	dup,
+	dup2,
+	pause,
+	nanosleep,
+	getitimer,
+	alarm,
+	setitimer,
+	getpid,
+	sendfile,
+	socket,
+	connect,
+	accept,
+	sendto,
+	recvfrom,
+	sendmsg,
+	recvmsg,
+	shutdown,
+	bind,
+	listen,
+	getsockname,
+	getpeername,
+	socketpair,
+	setsockopt,
+	getsockopt,
+	clone,
+	fork,
+	vfork,
+	execve,
+	exit,
+	wait4,
+	kill,
+	uname,
+	semget,
+	semop,
+	semctl,
+	shmdt,
+	msgget,
+	msgsnd,
+	msgrcv,
+	msgctl,
+	fcntl,
+	flock,
+	fsync,
+	fdatasync,
+	truncate,
+	ftruncate,
+	getdents,
+	getcwd,
+	chdir,
+	fchdir,
+	rename,
+	mkdir,
+	rmdir,
+	creat,
+	link,
+	unlink,
+	symlink,
+	readlink,
+	chmod,
+	fchmod,
+	chown,
+	fchown,
+	lchown,
+	umask,
+	gettimeofday,
+	getrlimit,
+	getrusage,
+	sysinfo,
+	times,
+	ptrace,
+	getuid,
+	syslog,
+	getgid,
+	setuid,
+	setgid,
+	geteuid,
+	getegid,
+	setpgid,
+	getppid,
+	getpgrp,
+	setsid,
+	setreuid,
+	setregid,
+	getgroups,
+	setgroups,
+	setresuid,
+	getresuid,
+	setresgid,
+	getresgid,
+	getpgid,
+	setfsuid,
+	setfsgid,
+	getsid,
+	capget,
+	capset,
+	rt_sigpending,
+	rt_sigtimedwait,
+	rt_sigqueueinfo,
+	rt_sigsuspend,
+	sigaltstack,
+	utime,
+	mknod,
+	personality,
+	statfs,
+	fstatfs,
+	getpriority,
+	setpriority,
+	sched_setparam,
+	sched_getparam,
+	sched_setscheduler,
+	sched_getscheduler,
+	sched_get_priority_max,
+	sched_get_priority_min,
+	mlock,
+	munlock,
+	mlockall,
+	munlockall,
+	vhangup,
+	prctl,
+	arch_prctl,
+	setrlimit,
+	chroot,
+	sync,
+	acct,
+	settimeofday,
+	mount,
+	umount2,
+	swapon,
+	swapoff,
+	reboot,
+	sethostname,
+	setdomainname,
+	init_module,
+	delete_module,
+	quotactl,
+	gettid,
+	readahead,
+	setxattr,
+	lsetxattr,
+	fsetxattr,
+	getxattr,
+	lgetxattr,
+	fgetxattr,
+	listxattr,
+	llistxattr,
+	flistxattr,
+	removexattr,
+	lremovexattr,
+	fremovexattr,
+	tkill,
+	time,
+	futex,
+	sched_setaffinity,
+	sched_getaffinity,
+	io_setup,
+	io_destroy,
+	io_getevents,
+	io_submit,
+	io_cancel,
+	epoll_create,
+	getdents64,
+	set_tid_address,
+	restart_syscall,
+	semtimedop,
+	fadvise64,
+	timer_create,
+	timer_settime,
+	timer_gettime,
+	timer_getoverrun,
+	timer_delete,
+	clock_settime,
+	clock_gettime,
+	clock_getres,
+	clock_nanosleep,
+	exit_group,
+	epoll_wait,
+	epoll_ctl,
+	tgkill,
+	mbind,
+	waitid,
+	ioprio_set,
+	ioprio_get,
+	inotify_init,
+	inotify_add_watch,
+	inotify_rm_watch,
+	openat,
+	mkdirat,
+	mknodat,
+	fchownat,
+	unlinkat,
+	renameat,
+	linkat,
+	symlinkat,
+	readlinkat,
+	fchmodat,
+	faccessat,
+	pselect6,
+	ppoll,
+	unshare,
+	set_robust_list,
+	get_robust_list,
+	splice,
+	tee,
+	sync_file_range,
+	vmsplice,
+	utimensat,
+	epoll_pwait,
+	signalfd,
+	timerfd_create,
+	eventfd,
+	fallocate,
+	timerfd_settime,
+	timerfd_gettime,
+	accept4,
+	signalfd4,
+	eventfd2,
+	epoll_create1,
+	dup3,
+	pipe2,
+	inotify_init1,
+	preadv,
+	pwritev,
+	rt_tgsigqueueinfo,
+	perf_event_open,
+	recvmmsg,
+	prlimit64,
+	sendmmsg,
+	setns,
+	getcpu,
+	seccomp,
+	getrandom,
+	memfd_create,
+	execveat,
+	membarrier,
+	mlock2,
+	copy_file_range,
+	preadv2,
+	pwritev2,
+	statx,
+}